@@ -1,16 +1,21 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PackageVersion {
     pub id: u64,
     pub name: String,
+    pub created_at: DateTime<Utc>,
     pub metadata: PackageVersionMetadata,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PackageVersionMetadata {
     pub package_type: String,
-    pub container: ContainerVersionMetadata,
+    /// Only populated for container package versions; other package types carry no
+    /// tag-like metadata at all.
+    #[serde(default)]
+    pub container: Option<ContainerVersionMetadata>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -1,14 +1,37 @@
 use std::fmt::Display;
+use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
 use reqwest::{
     header::{HeaderMap, ACCEPT, AUTHORIZATION, USER_AGENT},
-    Client, ClientBuilder,
+    Client, ClientBuilder, Response, StatusCode,
 };
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
 
 use super::PackageVersion;
 
+/// Maximum number of attempts (including the first) before giving up on a request.
+const MAX_ATTEMPTS: u32 = 6;
+
+/// Initial backoff used for transient server errors, doubled on every retry.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Upper bound for the exponential backoff applied to transient server errors.
+const MAX_BACKOFF: Duration = Duration::from_secs(32);
+
+/// Clock skew allowance applied to the `iat` claim of GitHub App JWTs.
+const JWT_CLOCK_SKEW: ChronoDuration = ChronoDuration::seconds(60);
+
+/// Lifetime of App JWTs, counted from `iat`; this is exactly GitHub's 10 minute cap.
+const JWT_EXPIRATION: ChronoDuration = ChronoDuration::seconds(600);
+
+/// Re-mint the installation token this long before it actually expires.
+const INSTALLATION_TOKEN_SAFETY_MARGIN: ChronoDuration = ChronoDuration::seconds(60);
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum PackageOwner {
     User(String),
@@ -45,41 +68,331 @@ impl Display for PackageOwner {
     }
 }
 
+/// A GitHub Packages ecosystem. Determines which REST endpoint a package lives under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[clap(rename_all = "lower")]
+pub enum PackageType {
+    Container,
+    Docker,
+    Npm,
+    Maven,
+    Rubygems,
+    Nuget,
+}
+
+impl PackageType {
+    fn url_segment(&self) -> &'static str {
+        match self {
+            Self::Container => "container",
+            Self::Docker => "docker",
+            Self::Npm => "npm",
+            Self::Maven => "maven",
+            Self::Rubygems => "rubygems",
+            Self::Nuget => "nuget",
+        }
+    }
+}
+
+impl Display for PackageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.url_segment())
+    }
+}
+
+/// How a [`GithubClientImpl`] authenticates its requests.
+enum Auth {
+    /// A verbatim, long-lived personal access token.
+    Token(String),
+    /// A GitHub App that mints short-lived installation tokens on demand.
+    App(AppAuth),
+}
+
+struct AppAuth {
+    app_id: u64,
+    installation_id: u64,
+    encoding_key: EncodingKey,
+    installation_token: Mutex<Option<InstallationToken>>,
+}
+
+struct InstallationToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct AppJwtClaims {
+    iat: i64,
+    exp: i64,
+    iss: String,
+}
+
+#[derive(Deserialize)]
+struct InstallationTokenResponse {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl AppAuth {
+    /// Return a valid installation token, re-minting it if there is none yet or it is about
+    /// to expire.
+    async fn access_token(&self, client: &Client) -> Result<String> {
+        let mut installation_token = self.installation_token.lock().await;
+
+        if let Some(token) = installation_token.as_ref() {
+            if token.expires_at - INSTALLATION_TOKEN_SAFETY_MARGIN > Utc::now() {
+                return Ok(token.token.clone());
+            }
+        }
+
+        log::debug!("Minting a new github app installation token");
+
+        let jwt = self.mint_jwt()?;
+        let response = client
+            .post(format!(
+                "https://api.github.com/app/installations/{}/access_tokens",
+                self.installation_id,
+            ))
+            .header(AUTHORIZATION, format!("Bearer {jwt}"))
+            .send()
+            .await
+            .context("Failed to request a github app installation token")?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Github returned status {} while minting an installation token",
+                response.status(),
+            ));
+        }
+
+        let parsed: InstallationTokenResponse = response
+            .json()
+            .await
+            .context("Failed to parse installation token response")?;
+
+        let token = parsed.token.clone();
+        *installation_token = Some(InstallationToken {
+            token: parsed.token,
+            expires_at: parsed.expires_at,
+        });
+
+        Ok(token)
+    }
+
+    fn mint_jwt(&self) -> Result<String> {
+        let iat = Utc::now() - JWT_CLOCK_SKEW;
+        let claims = AppJwtClaims {
+            iat: iat.timestamp(),
+            // Based on `iat`, not `now`, so `exp - iat` stays within GitHub's 10 minute cap
+            // even once the clock-skew allowance is taken into account.
+            exp: (iat + JWT_EXPIRATION).timestamp(),
+            iss: self.app_id.to_string(),
+        };
+
+        jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.encoding_key)
+            .context("Failed to sign github app JWT")
+    }
+}
+
 pub struct GithubClientImpl {
     client: Client,
+    auth: Auth,
 }
 
 impl GithubClientImpl {
     pub fn new(token: impl AsRef<str>) -> Result<Self> {
+        let client = Self::build_http_client()?;
+        Ok(Self {
+            client,
+            auth: Auth::Token(token.as_ref().to_string()),
+        })
+    }
+
+    /// Authenticate as a GitHub App installation instead of a verbatim token. `private_key_pem`
+    /// is the App's RSA private key in PEM format, used to sign short-lived JWTs that are
+    /// exchanged for installation access tokens as they expire.
+    pub fn new_app_auth(
+        app_id: u64,
+        installation_id: u64,
+        private_key_pem: impl AsRef<str>,
+    ) -> Result<Self> {
+        let encoding_key = EncodingKey::from_rsa_pem(private_key_pem.as_ref().as_bytes())
+            .context("Failed to parse the github app private key")?;
+        let client = Self::build_http_client()?;
+        Ok(Self {
+            client,
+            auth: Auth::App(AppAuth {
+                app_id,
+                installation_id,
+                encoding_key,
+                installation_token: Mutex::new(None),
+            }),
+        })
+    }
+
+    fn build_http_client() -> Result<Client> {
         let user_agent = format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
         log::debug!("{}: {}", USER_AGENT.as_str(), user_agent);
 
         let mut headers = HeaderMap::new();
         headers.insert(ACCEPT, "application/vnd.github.v3+json".try_into()?);
-        headers.insert(
-            AUTHORIZATION,
-            format!("Bearer {}", token.as_ref()).try_into()?,
-        );
         headers.insert(USER_AGENT, user_agent.try_into()?);
 
-        let client = ClientBuilder::new().default_headers(headers).build()?;
-        Ok(Self { client })
+        Ok(ClientBuilder::new().default_headers(headers).build()?)
+    }
+
+    /// Resolve the current `Authorization` header value. When authenticating as a GitHub App,
+    /// a new installation token is minted first if there is none yet or it is about to expire.
+    async fn auth_header(&self) -> Result<String> {
+        match &self.auth {
+            Auth::Token(token) => Ok(format!("Bearer {token}")),
+            Auth::App(app) => Ok(format!("Bearer {}", app.access_token(&self.client).await?)),
+        }
+    }
+
+    /// Send a request built by `build_request`, transparently retrying on GitHub's
+    /// secondary rate limits (429, and 403 when it actually carries a rate-limit signal)
+    /// and transient server errors (5xx).
+    ///
+    /// Rate limits are honored via the `Retry-After` header when present, falling back to
+    /// `X-RateLimit-Reset` when the response indicates the quota is exhausted
+    /// (`X-RateLimit-Remaining: 0`). Server errors are retried with capped exponential backoff.
+    /// A `403` with neither signal is a genuine permission error, not a rate limit, so it is
+    /// returned immediately instead of being retried.
+    async fn send_with_retry(
+        &self,
+        build_request: impl Fn(&Client) -> reqwest::RequestBuilder,
+    ) -> Result<Response> {
+        let mut backoff = INITIAL_BACKOFF;
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            let response = build_request(&self.client)
+                .send()
+                .await
+                .context("Failed to send request")?;
+            let status = response.status();
+
+            let rate_limit_wait = retry_after(&response).or_else(|| rate_limit_reset(&response));
+            let is_rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+                || (status == StatusCode::FORBIDDEN && rate_limit_wait.is_some());
+
+            if is_rate_limited {
+                if attempt == MAX_ATTEMPTS {
+                    return Err(anyhow!(
+                        "Exhausted retries after being rate limited by github (status {})",
+                        status,
+                    ));
+                }
+
+                let wait = rate_limit_wait.unwrap_or(backoff);
+                log::warn!(
+                    "Rate limited by github (status {}), retrying in {:?}",
+                    status,
+                    wait,
+                );
+                tokio::time::sleep(wait).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                continue;
+            }
+
+            if status == StatusCode::FORBIDDEN {
+                // No Retry-After and quota not exhausted: this is a real permission error
+                // (missing scope, unauthorized SSO, package not owned, ...), so fail fast
+                // instead of burning through every retry attempt.
+                return Err(anyhow!("Github denied the request (status 403)"));
+            }
+
+            if status.is_server_error() {
+                if attempt == MAX_ATTEMPTS {
+                    return Err(anyhow!(
+                        "Exhausted retries after server returned status {}",
+                        status,
+                    ));
+                }
+
+                log::warn!(
+                    "Server returned status {}, retrying in {:?}",
+                    status,
+                    backoff,
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                continue;
+            }
+
+            return Ok(response);
+        }
+
+        unreachable!("loop either returns or bails out on the last attempt")
     }
 }
 
+/// Parse the `Retry-After` header, which GitHub sends as a number of seconds to wait.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// When the rate limit quota is exhausted, GitHub reports the UNIX timestamp at which it
+/// resets via `X-RateLimit-Reset`. Returns `None` unless `X-RateLimit-Remaining` is `0`.
+fn rate_limit_reset(response: &Response) -> Option<Duration> {
+    let headers = response.headers();
+
+    let remaining = headers.get("x-ratelimit-remaining")?.to_str().ok()?;
+    if remaining != "0" {
+        return None;
+    }
+
+    let reset_at = headers
+        .get("x-ratelimit-reset")?
+        .to_str()
+        .ok()?
+        .parse::<u64>()
+        .ok()?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+
+    Some(Duration::from_secs(reset_at.saturating_sub(now)))
+}
+
+/// Extract the `rel="next"` URL from a comma-separated `Link` header, per RFC 8288, as GitHub
+/// sends it for paginated list endpoints.
+fn next_page_url(response: &Response) -> Option<String> {
+    let header = response.headers().get(reqwest::header::LINK)?.to_str().ok()?;
+
+    header.split(',').find_map(|entry| {
+        let mut parts = entry.split(';').map(str::trim);
+        let url = parts.next()?.trim_start_matches('<').trim_end_matches('>');
+        let is_next = parts.any(|param| param == "rel=\"next\"");
+        is_next.then(|| url.to_string())
+    })
+}
+
 #[async_trait]
 #[cfg_attr(test, mockall::automock)]
 pub trait GithubClient {
+    /// Fetch one page of package versions. Pass `None` to fetch the first page; pass the URL
+    /// returned alongside a previous page to follow `Link: rel="next"` to the one after it.
+    /// Returns the versions on that page together with the next page's URL, if any.
     async fn get_package_version(
         &self,
         owner: &PackageOwner,
+        package_type: PackageType,
         package_name: &str,
-        page: Option<u32>,
-    ) -> Result<Vec<PackageVersion>>;
+        url: Option<&str>,
+    ) -> Result<(Vec<PackageVersion>, Option<String>)>;
 
     async fn delete_package_version(
         &self,
         owner: &PackageOwner,
+        package_type: PackageType,
         package_name: &str,
         version_id: &str,
     ) -> Result<()>;
@@ -90,19 +403,20 @@ impl GithubClient for GithubClientImpl {
     async fn get_package_version(
         &self,
         owner: &PackageOwner,
+        package_type: PackageType,
         package_name: &str,
-        page: Option<u32>,
-    ) -> Result<Vec<PackageVersion>> {
-        let response = self
-            .client
-            .get(format!(
-                "https://api.github.com/{base}/packages/container/{package_name}/versions?page={page}",
+        url: Option<&str>,
+    ) -> Result<(Vec<PackageVersion>, Option<String>)> {
+        let url = url.map(str::to_string).unwrap_or_else(|| {
+            format!(
+                "https://api.github.com/{base}/packages/{package_type}/{package_name}/versions?per_page=100",
                 base = owner.base_url(),
-                page = page.unwrap_or(1),
-            ))
-            .send()
-            .await
-            .context("Failed to send request")?;
+            )
+        });
+        let auth_header = self.auth_header().await?;
+        let response = self
+            .send_with_retry(|client| client.get(&url).header(AUTHORIZATION, auth_header.as_str()))
+            .await?;
 
         if response.status().as_u16() == 404 {
             return Err(anyhow!("Package {}/{} does not exist", owner, package_name));
@@ -110,29 +424,32 @@ impl GithubClient for GithubClientImpl {
             return Err(anyhow!("Server returned status {}", response.status()));
         }
 
+        let next = next_page_url(&response);
         let versions = response
             .json()
             .await
             .context("Failed to parse reply as json")?;
 
-        Ok(versions)
+        Ok((versions, next))
     }
 
     async fn delete_package_version(
         &self,
         owner: &PackageOwner,
+        package_type: PackageType,
         package_name: &str,
         version_id: &str,
     ) -> Result<()> {
+        let url = format!(
+            "https://api.github.com/{base}/packages/{package_type}/{package_name}/versions/{version_id}",
+            base = owner.base_url(),
+        );
+        let auth_header = self.auth_header().await?;
         // The endpoint always returns 204 even if the version id is invalid.
-        self.client
-            .delete(format!(
-                "https://api.github.com/{base}/packages/container/{package_name}/versions/{version_id}",
-                base = owner.base_url(),
-            ))
-            .send()
-            .await
-            .context("Failed to send request")?;
+        self.send_with_retry(|client| {
+            client.delete(&url).header(AUTHORIZATION, auth_header.as_str())
+        })
+        .await?;
         Ok(())
     }
 }
@@ -0,0 +1,5 @@
+mod api;
+mod client;
+
+pub use api::*;
+pub use client::*;
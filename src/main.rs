@@ -1,14 +1,19 @@
 use std::env;
+use std::time::Duration as StdDuration;
 
 use anyhow::{anyhow, Context, Result};
+use chrono::{Duration as ChronoDuration, Utc};
 use clap::Parser;
-use github::{GithubClientImpl, PackageOwner, PackageVersion};
+use futures::stream::{FuturesUnordered, StreamExt};
+use github::{GithubClientImpl, PackageOwner, PackageType, PackageVersion};
+use regex::Regex;
+use tokio::sync::Semaphore;
 
 use crate::github::GithubClient;
 
 mod github;
 
-/// Delete all untagged versions of GitHub container packages.
+/// Delete versions of GitHub packages.
 #[derive(Parser)]
 #[clap(version)]
 struct Args {
@@ -22,13 +27,56 @@ struct Args {
 
     /// Path to a file containing a GitHub token.
     /// You can also pass a token verbatim via the GITHUB_TOKEN env variable.
-    #[clap(long)]
+    #[clap(long, conflicts_with = "app_id")]
     token: Option<String>,
 
+    /// GitHub App id, authenticates as an App installation instead of a token
+    /// (conflicts with --token, requires --installation-id and --private-key)
+    #[clap(long, requires = "installation_id", requires = "private_key")]
+    app_id: Option<u64>,
+
+    /// GitHub App installation id (used together with --app-id)
+    #[clap(long, conflicts_with = "token", requires = "app_id")]
+    installation_id: Option<u64>,
+
+    /// Path to a PEM-encoded RSA private key for the GitHub App (used together with --app-id)
+    #[clap(long, conflicts_with = "token", requires = "app_id")]
+    private_key: Option<String>,
+
+    /// GitHub Packages ecosystem the packages belong to
+    #[clap(long, value_enum, default_value_t = PackageType::Container)]
+    package_type: PackageType,
+
     /// Don't persist but only print changes
     #[clap(long, short = 'n')]
     dry_run: bool,
 
+    /// Number of package versions to delete concurrently
+    #[clap(long, default_value_t = 8, value_parser = parse_concurrency)]
+    concurrency: usize,
+
+    /// Keep the N most recently created eligible versions instead of deleting all of them
+    #[clap(long, default_value_t = 0)]
+    keep_last: usize,
+
+    /// Keep eligible versions created within this duration, e.g. "30d" or "12h"
+    #[clap(long, value_parser = humantime::parse_duration)]
+    keep_within: Option<StdDuration>,
+
+    /// Delete versions with no tags at all (container packages only)
+    #[clap(long)]
+    untagged: bool,
+
+    /// Delete versions with a tag matching this regex (repeatable, composes with --untagged;
+    /// container packages only)
+    #[clap(long = "match-tag")]
+    match_tags: Vec<String>,
+
+    /// Never delete versions with a tag matching this regex (repeatable), even if
+    /// --match-tag also matches
+    #[clap(long = "exclude-tag")]
+    exclude_tags: Vec<String>,
+
     /// Make logging more verbose.
     /// You can also specify the log level via the RUST_LOG env variable.
     #[clap(long, short)]
@@ -39,6 +87,26 @@ struct Args {
     package_names: Vec<String>,
 }
 
+/// Resolved, package-agnostic cleanup policy derived from [`Args`].
+struct CleanOptions {
+    package_type: PackageType,
+    concurrency: usize,
+    keep_last: usize,
+    keep_within: Option<ChronoDuration>,
+    untagged: bool,
+    match_tags: Vec<Regex>,
+    exclude_tags: Vec<Regex>,
+    dry_run: bool,
+}
+
+/// Why a version was selected for deletion.
+enum EligibilityReason<'a> {
+    Untagged,
+    MatchedTag(&'a str),
+    /// The package type carries no tag metadata, so selection is entirely retention-driven.
+    NoTagMetadata,
+}
+
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
@@ -69,58 +137,172 @@ async fn run(args: Args) -> Result<()> {
         return Err(anyhow!("Neither --user nor --org was provided"));
     }
 
-    let token = match args.token {
-        Some(path) => tokio::fs::read_to_string(&path)
-            .await
-            .context(format!("Failed to read the github token from {}", path))?
-            .trim()
-            .to_string(),
-        None => env::var("GITHUB_TOKEN")
-            .context("No github token provided via --token or GITHUB_TOKEN")?,
+    let client = match args.app_id {
+        Some(app_id) => {
+            // Guaranteed by the `requires` relations on `Args::app_id`.
+            let installation_id = args.installation_id.expect("--installation-id is required");
+            let private_key_path = args.private_key.expect("--private-key is required");
+            let private_key = tokio::fs::read_to_string(&private_key_path)
+                .await
+                .context(format!(
+                    "Failed to read the github app private key from {}",
+                    private_key_path,
+                ))?;
+            GithubClientImpl::new_app_auth(app_id, installation_id, private_key)
+                .context("Failed to create github client")?
+        }
+        None => {
+            let token = match args.token {
+                Some(path) => tokio::fs::read_to_string(&path)
+                    .await
+                    .context(format!("Failed to read the github token from {}", path))?
+                    .trim()
+                    .to_string(),
+                None => env::var("GITHUB_TOKEN")
+                    .context("No github token provided via --token or GITHUB_TOKEN")?,
+            };
+            GithubClientImpl::new(token).context("Failed to create github client")?
+        }
     };
-    let client = GithubClientImpl::new(token).context("Failed to create github client")?;
 
     let owner = PackageOwner::parse(args.user, args.org);
+    let keep_within = args
+        .keep_within
+        .map(ChronoDuration::from_std)
+        .transpose()
+        .context("--keep-within is out of range")?;
+    let match_tags = compile_patterns(&args.match_tags).context("Invalid --match-tag regex")?;
+    let exclude_tags =
+        compile_patterns(&args.exclude_tags).context("Invalid --exclude-tag regex")?;
+
+    let options = CleanOptions {
+        package_type: args.package_type,
+        concurrency: args.concurrency,
+        keep_last: args.keep_last,
+        keep_within,
+        untagged: args.untagged,
+        match_tags,
+        exclude_tags,
+        dry_run: args.dry_run,
+    };
+
+    if no_deletion_mode_selected(&options) {
+        log::warn!(
+            "No deletion mode is selected (--untagged, --match-tag, --keep-last or \
+             --keep-within); nothing will be deleted",
+        );
+    }
 
     for package_name in args.package_names {
-        clean_package(&client, &owner, &package_name, args.dry_run)
+        clean_package(&client, &owner, &package_name, &options)
             .await
-            .context(format!(
-                "Failed to clean package {}/{}",
-                owner, package_name,
-            ))?;
+            .context(format!("Failed to clean package {}/{}", owner, package_name))?;
     }
 
     Ok(())
 }
 
+/// Parse `--concurrency`, rejecting 0 since an empty semaphore would make the deletion
+/// loop hang forever instead of making progress.
+fn parse_concurrency(raw: &str) -> Result<usize, String> {
+    let concurrency: usize = raw.parse().map_err(|_| format!("invalid number: {}", raw))?;
+    if concurrency == 0 {
+        return Err("--concurrency must be at least 1".to_string());
+    }
+    Ok(concurrency)
+}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|pattern| Regex::new(pattern).context(format!("Invalid regex {:?}", pattern)))
+        .collect()
+}
+
 async fn clean_package(
     client: &impl GithubClient,
     owner: &PackageOwner,
     package_name: &str,
-    dry_run: bool,
+    options: &CleanOptions,
 ) -> Result<()> {
     log::info!("Cleaning package {}/{}", owner, package_name);
 
-    let mut page = 1;
+    let mut all_versions = Vec::new();
+    let mut next_url = None;
     loop {
-        let versions = client
-            .get_package_version(owner, package_name, Some(page))
+        let (versions, next) = client
+            .get_package_version(owner, options.package_type, package_name, next_url.as_deref())
             .await
             .context("Failed to get package versions from github")?;
 
-        if versions.is_empty() {
+        log::debug!(
+            "Fetched {} version(s) of {}/{} (more pages: {})",
+            versions.len(),
+            owner,
+            package_name,
+            next.is_some(),
+        );
+        all_versions.extend(versions);
+
+        next_url = next;
+        if next_url.is_none() {
             break;
         }
+    }
 
-        clean_package_versions(client, owner, package_name, &versions, dry_run)
-            .await
-            .context("Failed to clean package versions")?;
+    clean_package_versions(client, owner, package_name, &all_versions, options)
+        .await
+        .context("Failed to clean package versions")?;
+
+    Ok(())
+}
 
-        page += 1;
+/// True if `options` can never select anything for deletion, regardless of which versions
+/// are passed in, so the caller can warn instead of silently doing nothing.
+fn no_deletion_mode_selected(options: &CleanOptions) -> bool {
+    match options.package_type {
+        // Container eligibility is independent of retention: without --untagged or
+        // --match-tag the base eligible set is already empty, no matter the retention policy.
+        PackageType::Container => {
+            !options.untagged && options.match_tags.is_empty()
+        }
+        _ => options.keep_last == 0 && options.keep_within.is_none(),
     }
+}
 
-    Ok(())
+/// Determine why, if at all, `version` is eligible for deletion under `options`.
+fn eligibility<'a>(
+    version: &'a PackageVersion,
+    options: &CleanOptions,
+) -> Option<EligibilityReason<'a>> {
+    // Only container packages carry tag metadata; everything else can only be
+    // selected through the retention policy. Require an explicit retention policy so that
+    // e.g. `gone --package-type npm PKG` doesn't delete every version by default.
+    let Some(container) = &version.metadata.container else {
+        let has_retention_policy = options.keep_last > 0 || options.keep_within.is_some();
+        return has_retention_policy.then_some(EligibilityReason::NoTagMetadata);
+    };
+
+    let tags = &container.tags;
+
+    if tags.is_empty() {
+        return options.untagged.then_some(EligibilityReason::Untagged);
+    }
+
+    if options.match_tags.is_empty() {
+        return None;
+    }
+
+    let excluded = tags
+        .iter()
+        .any(|tag| options.exclude_tags.iter().any(|pattern| pattern.is_match(tag)));
+    if excluded {
+        return None;
+    }
+
+    tags.iter()
+        .find(|tag| options.match_tags.iter().any(|pattern| pattern.is_match(tag)))
+        .map(|tag| EligibilityReason::MatchedTag(tag.as_str()))
 }
 
 async fn clean_package_versions(
@@ -128,33 +310,69 @@ async fn clean_package_versions(
     owner: &PackageOwner,
     package_name: &str,
     versions: &[PackageVersion],
-    dry_run: bool,
+    options: &CleanOptions,
 ) -> Result<()> {
-    for version in versions {
-        if !version.metadata.container.tags.is_empty() {
-            continue;
-        }
-
-        let dry_run_suffix = match dry_run {
+    let mut eligible: Vec<(&PackageVersion, EligibilityReason)> = versions
+        .iter()
+        .filter_map(|version| eligibility(version, options).map(|reason| (version, reason)))
+        .collect();
+    eligible.sort_by_key(|(version, _)| std::cmp::Reverse(version.created_at));
+
+    let now = Utc::now();
+    let prunable = eligible
+        .into_iter()
+        .skip(options.keep_last)
+        .filter(|(version, _)| {
+            options
+                .keep_within
+                .map(|window| now - version.created_at >= window)
+                .unwrap_or(true)
+        });
+
+    let semaphore = Semaphore::new(options.concurrency);
+    let mut deletions = FuturesUnordered::new();
+
+    for (version, reason) in prunable {
+        let reason = match reason {
+            EligibilityReason::Untagged => "untagged".to_string(),
+            EligibilityReason::MatchedTag(tag) => format!("tag {:?} matched", tag),
+            EligibilityReason::NoTagMetadata => "retention policy".to_string(),
+        };
+        let dry_run_suffix = match options.dry_run {
             true => " (DRY RUN)",
             false => "",
         };
         log::info!(
-            "Deleting {}/{}:{}{}",
+            "Deleting {}/{}:{}{} ({})",
             owner,
             package_name,
             version.name,
             dry_run_suffix,
+            reason,
         );
 
-        if dry_run {
+        if options.dry_run {
             continue;
         }
 
-        if let Err(error) = client
-            .delete_package_version(owner, package_name, &version.id.to_string())
-            .await
-        {
+        deletions.push(async {
+            let _permit = semaphore
+                .acquire()
+                .await
+                .expect("semaphore should never be closed");
+            client
+                .delete_package_version(
+                    owner,
+                    options.package_type,
+                    package_name,
+                    &version.id.to_string(),
+                )
+                .await
+        });
+    }
+
+    while let Some(result) = deletions.next().await {
+        if let Err(error) = result {
             log::warn!("{:?}\n", error);
         }
     }
@@ -169,6 +387,19 @@ mod tests {
     use super::*;
     use crate::github::{ContainerVersionMetadata, MockGithubClient, PackageVersionMetadata};
 
+    fn options(dry_run: bool) -> CleanOptions {
+        CleanOptions {
+            package_type: PackageType::Container,
+            concurrency: 8,
+            keep_last: 0,
+            keep_within: None,
+            untagged: true,
+            match_tags: vec![],
+            exclude_tags: vec![],
+            dry_run,
+        }
+    }
+
     #[tokio::test]
     async fn test_clean_package_versions() {
         let mut client = MockGithubClient::new();
@@ -177,10 +408,10 @@ mod tests {
         let org = PackageOwner::Organizaion("org".to_string());
 
         // No versions
-        clean_package_versions(&client, &user, "my-package", &[], true)
+        clean_package_versions(&client, &user, "my-package", &[], &options(true))
             .await
             .unwrap();
-        clean_package_versions(&client, &user, "my-package", &[], false)
+        clean_package_versions(&client, &user, "my-package", &[], &options(false))
             .await
             .unwrap();
 
@@ -188,17 +419,18 @@ mod tests {
         let versions = vec![PackageVersion {
             id: 1,
             name: "sha256:foobar1".to_string(),
+            created_at: Utc::now(),
             metadata: PackageVersionMetadata {
                 package_type: "container".to_string(),
-                container: ContainerVersionMetadata {
+                container: Some(ContainerVersionMetadata {
                     tags: vec!["some-tag".to_string()],
-                },
+                }),
             },
         }];
-        clean_package_versions(&client, &user, "my-package", &versions, true)
+        clean_package_versions(&client, &user, "my-package", &versions, &options(true))
             .await
             .unwrap();
-        clean_package_versions(&client, &user, "my-package", &versions, false)
+        clean_package_versions(&client, &user, "my-package", &versions, &options(false))
             .await
             .unwrap();
 
@@ -207,31 +439,38 @@ mod tests {
             PackageVersion {
                 id: 1,
                 name: "sha256:foobar1".to_string(),
+                created_at: Utc::now(),
                 metadata: PackageVersionMetadata {
                     package_type: "container".to_string(),
-                    container: ContainerVersionMetadata {
+                    container: Some(ContainerVersionMetadata {
                         tags: vec!["some-tag".to_string()],
-                    },
+                    }),
                 },
             },
             PackageVersion {
                 id: 2,
                 name: "sha256:foobar2".to_string(),
+                created_at: Utc::now(),
                 metadata: PackageVersionMetadata {
                     package_type: "container".to_string(),
-                    container: ContainerVersionMetadata { tags: vec![] },
+                    container: Some(ContainerVersionMetadata { tags: vec![] }),
                 },
             },
         ];
         client
             .expect_delete_package_version()
-            .with(eq(user.clone()), eq("my-package"), eq("2"))
-            .returning(|_, _, _| Box::pin(async { Ok(()) }));
-        clean_package_versions(&client, &user, "my-package", &versions, false)
+            .with(
+                eq(user.clone()),
+                eq(PackageType::Container),
+                eq("my-package"),
+                eq("2"),
+            )
+            .returning(|_, _, _, _| Box::pin(async { Ok(()) }));
+        clean_package_versions(&client, &user, "my-package", &versions, &options(false))
             .await
             .unwrap();
         client.checkpoint();
-        clean_package_versions(&client, &user, "my-package", &versions, true)
+        clean_package_versions(&client, &user, "my-package", &versions, &options(true))
             .await
             .unwrap();
 
@@ -240,43 +479,271 @@ mod tests {
             PackageVersion {
                 id: 1,
                 name: "sha256:foobar1".to_string(),
+                created_at: Utc::now(),
                 metadata: PackageVersionMetadata {
                     package_type: "container".to_string(),
-                    container: ContainerVersionMetadata {
+                    container: Some(ContainerVersionMetadata {
                         tags: vec!["some-tag".to_string()],
-                    },
+                    }),
                 },
             },
             PackageVersion {
                 id: 2,
                 name: "sha256:foobar2".to_string(),
+                created_at: Utc::now(),
                 metadata: PackageVersionMetadata {
                     package_type: "container".to_string(),
-                    container: ContainerVersionMetadata { tags: vec![] },
+                    container: Some(ContainerVersionMetadata { tags: vec![] }),
                 },
             },
             PackageVersion {
                 id: 3,
                 name: "sha256:foobar3".to_string(),
+                created_at: Utc::now(),
                 metadata: PackageVersionMetadata {
                     package_type: "container".to_string(),
-                    container: ContainerVersionMetadata { tags: vec![] },
+                    container: Some(ContainerVersionMetadata { tags: vec![] }),
                 },
             },
         ];
         client
             .expect_delete_package_version()
-            .with(eq(org.clone()), eq("my-package"), eq("2"))
-            .returning(|_, _, _| Box::pin(async { Ok(()) }));
+            .with(
+                eq(org.clone()),
+                eq(PackageType::Container),
+                eq("my-package"),
+                eq("2"),
+            )
+            .returning(|_, _, _, _| Box::pin(async { Ok(()) }));
         client
             .expect_delete_package_version()
-            .with(eq(org.clone()), eq("my-package"), eq("3"))
-            .returning(|_, _, _| Box::pin(async { Ok(()) }));
-        clean_package_versions(&client, &org, "my-package", &versions, false)
+            .with(
+                eq(org.clone()),
+                eq(PackageType::Container),
+                eq("my-package"),
+                eq("3"),
+            )
+            .returning(|_, _, _, _| Box::pin(async { Ok(()) }));
+        clean_package_versions(&client, &org, "my-package", &versions, &options(false))
             .await
             .unwrap();
         client.checkpoint();
-        clean_package_versions(&client, &org, "my-package", &versions, true)
+        clean_package_versions(&client, &org, "my-package", &versions, &options(true))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clean_package_versions_keep_last() {
+        let mut client = MockGithubClient::new();
+        let user = PackageOwner::User("user".to_string());
+
+        let now = Utc::now();
+        let versions = vec![
+            PackageVersion {
+                id: 1,
+                name: "sha256:foobar1".to_string(),
+                created_at: now - ChronoDuration::days(2),
+                metadata: PackageVersionMetadata {
+                    package_type: "container".to_string(),
+                    container: Some(ContainerVersionMetadata { tags: vec![] }),
+                },
+            },
+            PackageVersion {
+                id: 2,
+                name: "sha256:foobar2".to_string(),
+                created_at: now - ChronoDuration::days(1),
+                metadata: PackageVersionMetadata {
+                    package_type: "container".to_string(),
+                    container: Some(ContainerVersionMetadata { tags: vec![] }),
+                },
+            },
+        ];
+
+        // Keeping the most recently created untagged version leaves only the older one to delete.
+        client
+            .expect_delete_package_version()
+            .with(
+                eq(user.clone()),
+                eq(PackageType::Container),
+                eq("my-package"),
+                eq("1"),
+            )
+            .returning(|_, _, _, _| Box::pin(async { Ok(()) }));
+        let mut options = options(false);
+        options.keep_last = 1;
+        clean_package_versions(&client, &user, "my-package", &versions, &options)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clean_package_versions_keep_within() {
+        let client = MockGithubClient::new();
+        let user = PackageOwner::User("user".to_string());
+
+        let now = Utc::now();
+        let versions = vec![PackageVersion {
+            id: 1,
+            name: "sha256:foobar1".to_string(),
+            created_at: now,
+            metadata: PackageVersionMetadata {
+                package_type: "container".to_string(),
+                container: Some(ContainerVersionMetadata { tags: vec![] }),
+            },
+        }];
+
+        // A freshly created untagged version is within the retention window, so nothing is deleted.
+        let mut options = options(false);
+        options.keep_within = Some(ChronoDuration::days(30));
+        clean_package_versions(&client, &user, "my-package", &versions, &options)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clean_package_versions_match_tag() {
+        let mut client = MockGithubClient::new();
+        let user = PackageOwner::User("user".to_string());
+
+        let versions = vec![
+            PackageVersion {
+                id: 1,
+                name: "sha256:foobar1".to_string(),
+                created_at: Utc::now(),
+                metadata: PackageVersionMetadata {
+                    package_type: "container".to_string(),
+                    container: Some(ContainerVersionMetadata {
+                        tags: vec!["pr-123".to_string()],
+                    }),
+                },
+            },
+            PackageVersion {
+                id: 2,
+                name: "sha256:foobar2".to_string(),
+                created_at: Utc::now(),
+                metadata: PackageVersionMetadata {
+                    package_type: "container".to_string(),
+                    container: Some(ContainerVersionMetadata {
+                        tags: vec!["latest".to_string()],
+                    }),
+                },
+            },
+        ];
+
+        client
+            .expect_delete_package_version()
+            .with(
+                eq(user.clone()),
+                eq(PackageType::Container),
+                eq("my-package"),
+                eq("1"),
+            )
+            .returning(|_, _, _, _| Box::pin(async { Ok(()) }));
+
+        let mut options = options(false);
+        options.untagged = false;
+        options.match_tags = vec![Regex::new("^pr-").unwrap()];
+        clean_package_versions(&client, &user, "my-package", &versions, &options)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clean_package_versions_exclude_tag() {
+        let client = MockGithubClient::new();
+        let user = PackageOwner::User("user".to_string());
+
+        let versions = vec![PackageVersion {
+            id: 1,
+            name: "sha256:foobar1".to_string(),
+            created_at: Utc::now(),
+            metadata: PackageVersionMetadata {
+                package_type: "container".to_string(),
+                container: Some(ContainerVersionMetadata {
+                    tags: vec!["pr-123".to_string(), "latest".to_string()],
+                }),
+            },
+        }];
+
+        // Excluded tags win even if another tag on the same version matches.
+        let mut options = options(false);
+        options.untagged = false;
+        options.match_tags = vec![Regex::new("^pr-").unwrap()];
+        options.exclude_tags = vec![Regex::new("^latest$").unwrap()];
+        clean_package_versions(&client, &user, "my-package", &versions, &options)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clean_package_versions_non_container_uses_retention_only() {
+        let mut client = MockGithubClient::new();
+        let user = PackageOwner::User("user".to_string());
+
+        let now = Utc::now();
+        let versions = vec![
+            PackageVersion {
+                id: 1,
+                name: "1.0.0".to_string(),
+                created_at: now - ChronoDuration::days(2),
+                metadata: PackageVersionMetadata {
+                    package_type: "npm".to_string(),
+                    container: None,
+                },
+            },
+            PackageVersion {
+                id: 2,
+                name: "1.1.0".to_string(),
+                created_at: now - ChronoDuration::days(1),
+                metadata: PackageVersionMetadata {
+                    package_type: "npm".to_string(),
+                    container: None,
+                },
+            },
+        ];
+
+        // Even with --untagged unset, npm versions have no tags to key off of, so keeping the
+        // most recent one and deleting the rest is entirely retention-driven.
+        client
+            .expect_delete_package_version()
+            .with(
+                eq(user.clone()),
+                eq(PackageType::Npm),
+                eq("my-package"),
+                eq("1"),
+            )
+            .returning(|_, _, _, _| Box::pin(async { Ok(()) }));
+        let mut options = options(false);
+        options.package_type = PackageType::Npm;
+        options.untagged = false;
+        options.keep_last = 1;
+        clean_package_versions(&client, &user, "my-package", &versions, &options)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clean_package_versions_non_container_without_retention_policy_keeps_all() {
+        let client = MockGithubClient::new();
+        let user = PackageOwner::User("user".to_string());
+
+        let versions = vec![PackageVersion {
+            id: 1,
+            name: "1.0.0".to_string(),
+            created_at: Utc::now(),
+            metadata: PackageVersionMetadata {
+                package_type: "npm".to_string(),
+                container: None,
+            },
+        }];
+
+        // No --keep-last/--keep-within means no explicit retention policy, so nothing is
+        // eligible rather than everything.
+        let mut options = options(false);
+        options.package_type = PackageType::Npm;
+        options.untagged = false;
+        clean_package_versions(&client, &user, "my-package", &versions, &options)
             .await
             .unwrap();
     }